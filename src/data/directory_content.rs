@@ -1,7 +1,6 @@
 use crate::config::{FileDialogConfig, FileFilter};
 use crate::FileSystem;
 use egui::mutex::Mutex;
-use poll_promise::Promise;
 use std::path::{Path, PathBuf};
 use std::sync::{mpsc, Arc};
 use std::time::SystemTime;
@@ -15,6 +14,9 @@ pub struct Metadata {
     pub(crate) last_modified: Option<SystemTime>,
     pub(crate) created: Option<SystemTime>,
     pub(crate) file_type: Option<String>,
+    /// Resolved MIME type of the item, if one could be determined.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) mime: Option<String>,
 }
 
 impl Metadata {
@@ -30,8 +32,14 @@ impl Metadata {
             last_modified,
             created,
             file_type,
+            mime: None,
         }
     }
+
+    /// Returns the resolved MIME type of the item, for example `image/png`.
+    pub fn mime(&self) -> Option<&str> {
+        self.mime.as_deref()
+    }
 }
 
 /// Contains the information of a directory item.
@@ -54,17 +62,30 @@ pub struct DirectoryEntry {
 impl DirectoryEntry {
     /// Creates a new directory entry from a path
     pub fn from_path(config: &FileDialogConfig, path: &Path, file_system: &dyn FileSystem) -> Self {
+        let mut metadata = file_system.metadata(path).unwrap_or_default();
+        let is_directory = file_system.is_dir(path);
+
+        if !is_directory {
+            metadata.mime = resolve_mime(config, path, file_system);
+        }
+
         Self {
             path: path.to_path_buf(),
-            metadata: file_system.metadata(path).unwrap_or_default(),
-            is_directory: file_system.is_dir(path),
-            is_system_file: !file_system.is_dir(path) && !file_system.is_file(path),
-            icon: gen_path_icon(config, path, file_system),
+            is_directory,
+            is_system_file: !is_directory && !file_system.is_file(path),
+            icon: gen_path_icon(config, path, file_system, metadata.mime.as_deref()),
             is_hidden: file_system.is_path_hidden(path),
+            metadata,
             selected: false,
         }
     }
 
+    /// Returns the resolved MIME type of the directory entry, for example
+    /// `image/png`. Only files carry a MIME type.
+    pub fn mime_type(&self) -> Option<&str> {
+        self.metadata.mime()
+    }
+
     /// Returns the metadata of the directory entry.
     pub const fn metadata(&self) -> &Metadata {
         &self.metadata
@@ -150,7 +171,6 @@ impl DirectoryEntry {
     }
 }
 
-/*
 /// Contains the state of the directory content.
 #[derive(Debug, PartialEq, Eq)]
 pub enum DirectoryContentState {
@@ -166,7 +186,108 @@ pub enum DirectoryContentState {
     /// The value contains the error message.
     Errored(String),
 }
-*/
+
+/// Determines which directory-entry property the content is ordered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum SortMode {
+    /// Order by file name using the natural (alphanumeric) comparator.
+    #[default]
+    Name,
+    /// Order by file size, falling back to the name comparator on ties.
+    Size,
+    /// Order by last modification time, falling back to the name comparator.
+    LastModified,
+    /// Order by creation time, falling back to the name comparator.
+    Created,
+    /// Order by the reported file type, falling back to the name comparator.
+    FileType,
+}
+
+/// Direction the [`SortMode`] is applied in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum SortDirection {
+    /// Smallest to largest / A to Z.
+    #[default]
+    Ascending,
+    /// Largest to smallest / Z to A.
+    Descending,
+}
+
+/// The subset of [`FileDialogConfig`] that controls how a directory's content
+/// is ordered. Captured when the content is created so the ordering can be
+/// applied again once a streamed listing finishes.
+#[derive(Debug, Clone, Copy)]
+struct SortSettings {
+    mode: SortMode,
+    direction: SortDirection,
+    /// Whether directories are grouped before files regardless of the mode.
+    group_directories: bool,
+    /// Whether the name comparator ignores case.
+    case_insensitive: bool,
+}
+
+impl Default for SortSettings {
+    fn default() -> Self {
+        Self {
+            mode: SortMode::default(),
+            direction: SortDirection::default(),
+            group_directories: true,
+            case_insensitive: false,
+        }
+    }
+}
+
+impl SortSettings {
+    fn from_config(config: &FileDialogConfig) -> Self {
+        Self {
+            mode: config.sort_mode,
+            direction: config.sort_direction,
+            group_directories: config.group_directories_first,
+            case_insensitive: config.sort_case_insensitive,
+        }
+    }
+}
+
+/// A file system change reported by [`crate::FileSystem::watch`].
+///
+/// Events are expected to be coalesced and debounced by the watcher before
+/// they reach the dialog, so each event maps to a single incremental update of
+/// the directory listing.
+#[derive(Debug, Clone)]
+pub enum FsEvent {
+    /// A new path appeared in the watched directory.
+    Created(PathBuf),
+    /// An existing path's metadata or contents changed.
+    Modified(PathBuf),
+    /// A path was removed from the watched directory.
+    Removed(PathBuf),
+    /// A path was renamed within the watched directory.
+    Renamed {
+        /// The previous path.
+        from: PathBuf,
+        /// The new path.
+        to: PathBuf,
+    },
+}
+
+/// The context a [`DirectoryContent`] keeps so it can rebuild individual
+/// entries in response to [`FsEvent`]s without re-scanning the whole
+/// directory.
+struct WatchContext {
+    config: FileDialogConfig,
+    path: PathBuf,
+    include_files: bool,
+    file_filter: Option<FileFilter>,
+    file_system: Arc<dyn FileSystem + Send + Sync + 'static>,
+    ignore_rules: Vec<IgnoreRule>,
+}
+
+/// Number of entries a loader thread buffers before handing a batch to the UI.
+/// Small enough that huge directories stream in visibly, large enough to keep
+/// the channel overhead negligible.
+const LOAD_BATCH_SIZE: usize = 1000;
 
 type DirectoryContentReceiver =
     Option<Arc<Mutex<mpsc::Receiver<Result<Vec<DirectoryEntry>, std::io::Error>>>>>;
@@ -174,29 +295,44 @@ type DirectoryContentReceiver =
 /// Contains the content of a directory.
 pub struct DirectoryContent {
     /// Current state of the directory content.
-    pub(crate) content: Promise<Result<Vec<DirectoryEntry>, String>>,
-    /// Timestamp of Promise creation
-    pub(crate) creation_time: SystemTime,
+    state: DirectoryContentState,
+    /// Entries discovered so far. Streamed in incrementally while the loader
+    /// thread is still walking the directory.
+    content: Vec<DirectoryEntry>,
+    /// Receiver the loader thread streams batches of entries over.
+    /// `None` once loading finished or when loaded synchronously.
+    content_recv: DirectoryContentReceiver,
+    /// How the content should be ordered once fully loaded.
+    sort: SortSettings,
+    /// Receiver of file system change events for the displayed directory.
+    /// `None` when the backing file system does not support watching.
+    watch_recv: Option<mpsc::Receiver<FsEvent>>,
+    /// State needed to rebuild entries in response to watch events.
+    watch: Option<WatchContext>,
+    /// Set when a synchronous load already finished at construction time, so
+    /// `state` starts out as `Finished` rather than transitioning there
+    /// during an `update()` call. Tells the first `update()` call to let that
+    /// `Finished` state through instead of immediately folding it back to
+    /// `Success`, so the one-shot transition is still observable.
+    sync_finished_pending: bool,
 }
 
 impl Default for DirectoryContent {
     fn default() -> Self {
         Self {
-            content: Promise::from_ready(Ok(vec![])),
-            creation_time: SystemTime::now(),
-            //state: DirectoryContentState::Success,
-            //content: Vec::new(),
-            //content_recv: None,
+            state: DirectoryContentState::Success,
+            content: Vec::new(),
+            content_recv: None,
+            sort: SortSettings::default(),
+            watch_recv: None,
+            watch: None,
+            sync_finished_pending: false,
         }
     }
 }
 
 impl std::fmt::Debug for DirectoryContent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // TODO
-        f.debug_struct("DirectoryContent")
-            .finish()
-        /*
         f.debug_struct("DirectoryContent")
             .field("state", &self.state)
             .field("content", &self.content)
@@ -209,7 +345,6 @@ impl std::fmt::Debug for DirectoryContent {
                 },
             )
             .finish()
-        */
     }
 }
 
@@ -223,11 +358,31 @@ impl DirectoryContent {
         file_filter: Option<&FileFilter>,
         file_system: Arc<dyn FileSystem + Sync + Send + 'static>,
     ) -> Self {
-        if config.load_via_thread {
-            Self::with_thread(config, path, include_files, file_filter, file_system)
+        let mut content = if config.load_via_thread {
+            Self::with_thread(config, path, include_files, file_filter, file_system.clone())
         } else {
             Self::without_thread(config, path, include_files, file_filter, &*file_system)
+        };
+
+        // Subscribe to file system changes so the listing refreshes
+        // automatically when files are added, removed or renamed on disk.
+        content.watch_recv = file_system.watch(path);
+        if content.watch_recv.is_some() {
+            content.watch = Some(WatchContext {
+                config: config.clone(),
+                path: path.to_path_buf(),
+                include_files,
+                file_filter: file_filter.cloned(),
+                ignore_rules: if config.respect_gitignore {
+                    build_ignore_rules(config, path, &*file_system)
+                } else {
+                    Vec::new()
+                },
+                file_system,
+            });
         }
+
+        content
     }
 
     fn with_thread(
@@ -241,13 +396,23 @@ impl DirectoryContent {
         let p = path.to_path_buf();
         let f = file_filter.cloned();
 
-        let content = Promise::spawn_thread("File dialog load", move || {
-            load_directory(&c, &p, include_files, f.as_ref(), &*file_system).map_err(|e| e.to_string())
-        });
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::Builder::new()
+            .name("File dialog load".to_owned())
+            .spawn(move || {
+                stream_directory(&c, &p, include_files, f.as_ref(), &*file_system, &tx);
+            })
+            .expect("failed to spawn file dialog load thread");
 
         Self {
-            content,
-            creation_time: SystemTime::now(),
+            state: DirectoryContentState::Pending(SystemTime::now()),
+            content: Vec::new(),
+            content_recv: Some(Arc::new(Mutex::new(rx))),
+            sort: SortSettings::from_config(config),
+            watch_recv: None,
+            watch: None,
+            sync_finished_pending: false,
         }
     }
 
@@ -258,9 +423,133 @@ impl DirectoryContent {
         file_filter: Option<&FileFilter>,
         file_system: &dyn FileSystem,
     ) -> Self {
-        Self {
-            content: Promise::from_ready(load_directory(config, path, include_files, file_filter, file_system).map_err(|e| e.to_string())),
-            creation_time: SystemTime::now(),
+        match load_directory(config, path, include_files, file_filter, file_system) {
+            Ok(content) => Self {
+                // Start in `Finished` rather than `Success` so the one-shot
+                // transition still fires for synchronous loads, same as the
+                // threaded path: callers that run a post-load action (like
+                // scrolling to the top) on `Finished` shouldn't have to
+                // special-case `load_via_thread == false`. `update()` uses
+                // `sync_finished_pending` to let this `Finished` state
+                // actually be observed once before folding it to `Success`.
+                state: DirectoryContentState::Finished,
+                content,
+                content_recv: None,
+                sort: SortSettings::from_config(config),
+                watch_recv: None,
+                watch: None,
+                sync_finished_pending: true,
+            },
+            Err(err) => Self {
+                state: DirectoryContentState::Errored(err.to_string()),
+                content: Vec::new(),
+                content_recv: None,
+                sort: SortSettings::from_config(config),
+                watch_recv: None,
+                watch: None,
+                sync_finished_pending: false,
+            },
+        }
+    }
+
+    /// Returns the current state of the directory content.
+    pub const fn state(&self) -> &DirectoryContentState {
+        &self.state
+    }
+
+    /// Drains any entries the loader thread has streamed since the last frame,
+    /// appending them to the content and advancing the loading state.
+    ///
+    /// Returns the updated state. The `Finished` state is only returned once,
+    /// on the first call after the loader thread completed, so callers can run
+    /// a one-shot action (like scrolling to the top) when loading is done.
+    pub fn update(&mut self) -> &DirectoryContentState {
+        // `Finished` is a one-shot transition to `Success`. A synchronous
+        // load starts life already `Finished`, so the very first call must
+        // let that through unreset, or `Finished` would never be observable
+        // through this method's return value for that path.
+        if self.state == DirectoryContentState::Finished {
+            if self.sync_finished_pending {
+                self.sync_finished_pending = false;
+            } else {
+                self.state = DirectoryContentState::Success;
+            }
+        }
+
+        let mut finished = false;
+
+        if let Some(recv) = self.content_recv.clone() {
+            let recv = recv.lock();
+
+            loop {
+                match recv.try_recv() {
+                    Ok(Ok(mut batch)) => self.content.append(&mut batch),
+                    Ok(Err(err)) => {
+                        self.state = DirectoryContentState::Errored(err.to_string());
+                        finished = true;
+                        break;
+                    }
+                    Err(mpsc::TryRecvError::Empty) => break,
+                    Err(mpsc::TryRecvError::Disconnected) => {
+                        finished = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        if finished {
+            self.content_recv = None;
+
+            if !matches!(self.state, DirectoryContentState::Errored(_)) {
+                // Entries stream in unsorted, so apply the ordering once the
+                // full listing is available.
+                sort_content(&self.sort, &mut self.content);
+                self.state = DirectoryContentState::Finished;
+            }
+        }
+
+        // Only reconcile watch events once the directory has been fully
+        // loaded. Applying them while entries are still streaming in from
+        // the loader thread would race the loader: an event for a path it
+        // hasn't reached yet gets upserted here and then appended again when
+        // the loader streams it, duplicating the entry (and a `Removed`
+        // racing the loader similarly could resurrect a deleted one).
+        if matches!(
+            self.state,
+            DirectoryContentState::Success | DirectoryContentState::Finished
+        ) {
+            self.poll_watch();
+        }
+
+        &self.state
+    }
+
+    /// Drains any pending file system change events and applies them to the
+    /// content, re-sorting only when something actually changed. Called every
+    /// frame from [`Self::update`] once loading has finished.
+    fn poll_watch(&mut self) {
+        let Some(recv) = &self.watch_recv else {
+            return;
+        };
+
+        let events: Vec<FsEvent> = recv.try_iter().collect();
+        if events.is_empty() {
+            return;
+        }
+
+        let Some(ctx) = &self.watch else {
+            return;
+        };
+
+        let sort = self.sort;
+        let mut changed = false;
+        for event in events {
+            changed |= apply_fs_event(ctx, &mut self.content, event);
+        }
+
+        if changed {
+            sort_content(&sort, &mut self.content);
         }
     }
 
@@ -270,10 +559,7 @@ impl DirectoryContent {
         &mut self,
         range: std::ops::Range<usize>,
     ) -> impl Iterator<Item = &mut DirectoryEntry> {
-        match self.content.ready_mut() {
-            Some(Ok(dirs)) => &mut dirs[range],
-            _ => &mut [],
-        }.iter_mut()
+        self.content[range].iter_mut()
     }
 
     /// Returns an iterator in the given range of the directory cotnents.
@@ -281,20 +567,14 @@ impl DirectoryContent {
     pub fn iter_mut(
         &mut self,
     ) -> impl Iterator<Item = &mut DirectoryEntry> {
-        match self.content.ready_mut() {
-            Some(Ok(dirs)) => &mut dirs[..],
-            _ => &mut [],
-        }.iter_mut()
+        self.content.iter_mut()
     }
 
     /// Returns an iterator over the directory cotnents.
     pub fn iter(
         &self,
     ) -> impl Iterator<Item = &DirectoryEntry> {
-        match self.content.ready() {
-            Some(Ok(dirs)) => &dirs[..],
-            _ => &[],
-        }.iter()
+        self.content.iter()
     }
 
     pub fn filtered_iter<'s>(
@@ -322,17 +602,12 @@ impl DirectoryContent {
 
     /// Returns the number of elements inside the directory.
     pub fn len(&self) -> usize {
-        match self.content.ready() {
-            Some(Ok(content)) => content.len(),
-            _ => 0,
-        }
+        self.content.len()
     }
 
     /// Pushes a new item to the content.
     pub fn push(&mut self, item: DirectoryEntry) {
-        if let Some(Ok(content)) = self.content.ready_mut() {
-            content.push(item);
-        }
+        self.content.push(item);
     }
 }
 
@@ -344,6 +619,530 @@ fn apply_search_value(entry: &DirectoryEntry, value: &str) -> bool {
             .contains(&value.to_lowercase())
 }
 
+/// A single compiled gitignore-style rule.
+///
+/// Rules keep the directory their ignore file lived in (`base`) so the entry
+/// path can be tested relative to it. A list of rules is evaluated in order;
+/// the last rule that matches an entry decides whether it is ignored, with
+/// `!`-prefixed (negation) rules re-including a previously ignored entry.
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    /// The pattern body, without a leading `!` or trailing `/`.
+    pattern: String,
+    /// Whether the rule starts with `!` and therefore re-includes a match.
+    is_negation: bool,
+    /// Whether the pattern is anchored to `base` (a leading or embedded `/`)
+    /// rather than matching an entry's file name at any depth.
+    anchored: bool,
+    /// Whether a trailing `/` restricts the rule to directories.
+    dir_only: bool,
+    /// Directory the originating ignore file lived in.
+    base: PathBuf,
+}
+
+impl IgnoreRule {
+    /// Parses a single ignore-file line into a rule, relative to `base`.
+    /// Returns `None` for blank lines and comments.
+    fn parse(line: &str, base: &Path) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let mut pattern = line;
+
+        let is_negation = pattern.starts_with('!');
+        if is_negation {
+            pattern = &pattern[1..];
+        }
+
+        let dir_only = pattern.ends_with('/');
+        if dir_only {
+            pattern = &pattern[..pattern.len() - 1];
+        }
+
+        // A leading slash anchors to the base and is otherwise dropped; any
+        // other embedded slash also anchors the pattern to the base.
+        let anchored = pattern.starts_with('/') || pattern.trim_end_matches('/').contains('/');
+        let pattern = pattern.trim_start_matches('/');
+
+        if pattern.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            pattern: pattern.to_owned(),
+            is_negation,
+            anchored,
+            dir_only,
+            base: base.to_path_buf(),
+        })
+    }
+
+    /// Returns whether this rule matches the given entry.
+    ///
+    /// A rule that matches an ancestor directory also matches everything below
+    /// it, so the contents of an ignored directory stay hidden once the user
+    /// descends into it (`/target` hides `target/` at the base and every path
+    /// inside it). `dir_only` only constrains the entry the rule matches
+    /// directly; ancestor matches are always directories.
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.base) else {
+            return false;
+        };
+
+        let relative: Vec<&str> = relative
+            .iter()
+            .filter_map(|component| component.to_str())
+            .collect();
+
+        if relative.is_empty() {
+            return false;
+        }
+
+        if self.anchored {
+            let pattern: Vec<&str> = self.pattern.split('/').collect();
+            // A full match tests the entry itself and honours `dir_only`.
+            if match_segments(&pattern, &relative) {
+                return !(self.dir_only && !is_dir);
+            }
+            // Otherwise an ancestor directory matching the pattern ignores the
+            // descendant too.
+            (1..relative.len()).any(|k| match_segments(&pattern, &relative[..k]))
+        } else {
+            // Non-anchored patterns match a file name at any depth; a match on
+            // an ancestor segment ignores the descendant too.
+            relative.iter().enumerate().any(|(i, name)| {
+                if !wildcard_match(&self.pattern, name) {
+                    return false;
+                }
+                // `dir_only` only applies when the match is the entry itself.
+                !(self.dir_only && i + 1 == relative.len() && !is_dir)
+            })
+        }
+    }
+}
+
+/// Matches a slash-separated pattern against path segments, where a `**`
+/// segment matches zero or more path segments.
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => {
+            if rest.is_empty() {
+                return true;
+            }
+            (0..=path.len()).any(|i| match_segments(rest, &path[i..]))
+        }
+        Some((seg, rest)) => match path.split_first() {
+            Some((head, tail)) if wildcard_match(seg, head) => match_segments(rest, tail),
+            _ => false,
+        },
+    }
+}
+
+/// Matches a single path segment against a glob pattern supporting `*`
+/// (any run of non-separator characters) and `?` (a single character).
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+
+    fn inner(pattern: &[char], value: &[char]) -> bool {
+        match pattern.split_first() {
+            None => value.is_empty(),
+            Some(('*', rest)) => {
+                (0..=value.len()).any(|i| inner(rest, &value[i..]))
+            }
+            Some(('?', rest)) => !value.is_empty() && inner(rest, &value[1..]),
+            Some((c, rest)) => match value.split_first() {
+                Some((v, tail)) if v == c => inner(rest, tail),
+                _ => false,
+            },
+        }
+    }
+
+    inner(&pattern, &value)
+}
+
+/// Gathers the gitignore rules that apply to the directory being listed.
+///
+/// Ignore files (`.gitignore`, `.ignore`) are collected walking up from
+/// `path` to the repository root (the directory containing `.git`), together
+/// with the global excludes file and any extra patterns configured on the
+/// dialog. Rules are ordered from the outermost ignore file to the innermost
+/// so that deeper and user-supplied rules take precedence.
+fn build_ignore_rules(
+    config: &FileDialogConfig,
+    path: &Path,
+    file_system: &dyn FileSystem,
+) -> Vec<IgnoreRule> {
+    // Real git only walks ancestor `.gitignore`/`.ignore` files up to a
+    // discoverable repository root; outside a repo it doesn't climb at all.
+    // Find the root first (a cheap `.git` existence check) so the read loop
+    // below never has to visit, let alone read ignore files from, a
+    // directory outside the repo.
+    let repo_root = find_repo_root(path, file_system);
+
+    let mut levels: Vec<Vec<IgnoreRule>> = Vec::new();
+
+    let mut current = Some(path);
+    while let Some(dir) = current {
+        let mut rules = Vec::new();
+        for file_name in [".gitignore", ".ignore"] {
+            if let Ok(contents) = file_system.read_to_string(&dir.join(file_name)) {
+                rules.extend(contents.lines().filter_map(|line| IgnoreRule::parse(line, dir)));
+            }
+        }
+        levels.push(rules);
+
+        match &repo_root {
+            Some(root) if root == dir => break,
+            Some(_) => current = dir.parent(),
+            // Not inside a discoverable git repository: restrict to the
+            // listed directory's own ignore files instead of climbing to the
+            // filesystem root the way nothing outside a repo ever would.
+            None => break,
+        }
+    }
+
+    // Outermost ignore file first, innermost last.
+    let mut result: Vec<IgnoreRule> = levels.into_iter().rev().flatten().collect();
+
+    // The global excludes file applies to every repository, not just ones
+    // without a discoverable root, so honour it unconditionally, same as
+    // real git. It sits below per-repo `.gitignore`/`.ignore` rules in
+    // precedence.
+    if let Some(global) = global_excludes_file(file_system) {
+        if let Ok(contents) = file_system.read_to_string(&global) {
+            let mut prefix: Vec<IgnoreRule> = contents
+                .lines()
+                .filter_map(|line| IgnoreRule::parse(line, path))
+                .collect();
+            prefix.append(&mut result);
+            result = prefix;
+        }
+    }
+
+    // User-supplied patterns have the highest precedence.
+    result.extend(
+        config
+            .respect_gitignore_extra_patterns
+            .iter()
+            .filter_map(|line| IgnoreRule::parse(line, path)),
+    );
+
+    result
+}
+
+/// Walks up from `path` looking for a directory containing `.git`, returning
+/// it if found. Climbs all the way to the filesystem root if no repository
+/// is ever found.
+fn find_repo_root(path: &Path, file_system: &dyn FileSystem) -> Option<PathBuf> {
+    let mut current = Some(path);
+    while let Some(dir) = current {
+        if file_system.is_dir(&dir.join(".git")) {
+            return Some(dir.to_path_buf());
+        }
+        current = dir.parent();
+    }
+    None
+}
+
+/// Returns the path to the git global excludes file: the user's
+/// `core.excludesFile` override if one is set in their global git config,
+/// otherwise the conventional `$XDG_CONFIG_HOME/git/ignore` (or
+/// `~/.config/git/ignore`) default location.
+fn global_excludes_file(file_system: &dyn FileSystem) -> Option<PathBuf> {
+    excludes_file_override(file_system).or_else(|| {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|config| config.join("git").join("ignore"))
+    })
+}
+
+/// Reads `core.excludesFile` from the user's global git config
+/// (`$XDG_CONFIG_HOME/git/config`, falling back to `~/.gitconfig`), expanding
+/// a leading `~/` in the configured value.
+fn excludes_file_override(file_system: &dyn FileSystem) -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+
+    let xdg_config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(|dir| PathBuf::from(dir).join("git").join("config"));
+    let home_config = home.as_ref().map(|home| home.join(".gitconfig"));
+
+    let contents = [xdg_config, home_config]
+        .into_iter()
+        .flatten()
+        .find_map(|path| file_system.read_to_string(&path).ok())?;
+    let value = parse_excludes_file_setting(&contents)?;
+
+    if let Some(rest) = value.strip_prefix("~/") {
+        Some(home?.join(rest))
+    } else {
+        Some(PathBuf::from(value))
+    }
+}
+
+/// Extracts the value of `excludesfile` from the `[core]` section of a git
+/// config file's contents, without pulling in a full INI parser.
+fn parse_excludes_file_setting(contents: &str) -> Option<String> {
+    let mut in_core_section = false;
+    for line in contents.lines() {
+        let line = line.trim();
+        if let Some(section) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            in_core_section = section.eq_ignore_ascii_case("core");
+            continue;
+        }
+        if !in_core_section {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            if key.trim().eq_ignore_ascii_case("excludesfile") {
+                return Some(value.trim().to_owned());
+            }
+        }
+    }
+    None
+}
+
+/// Returns whether an entry is hidden by the active ignore rules.
+fn is_ignored(rules: &[IgnoreRule], entry: &DirectoryEntry) -> bool {
+    let mut ignored = false;
+    for rule in rules {
+        if rule.matches(entry.as_path(), entry.is_dir()) {
+            ignored = !rule.is_negation;
+        }
+    }
+    ignored
+}
+
+/// Returns whether the given entry passes the active content filters
+/// (system files, files vs directories, hidden files, ignore rules and the
+/// file filter).
+fn entry_passes_filters(
+    config: &FileDialogConfig,
+    entry: &DirectoryEntry,
+    include_files: bool,
+    file_filter: Option<&FileFilter>,
+    ignore_rules: &[IgnoreRule],
+) -> bool {
+    if !config.storage.show_system_files && entry.is_system_file() {
+        return false;
+    }
+
+    if !include_files && entry.is_file() {
+        return false;
+    }
+
+    if !config.storage.show_hidden && entry.is_hidden() {
+        return false;
+    }
+
+    if config.respect_gitignore && is_ignored(ignore_rules, entry) {
+        return false;
+    }
+
+    if let Some(file_filter) = file_filter {
+        if entry.is_file()
+            && !(file_filter.filter)(entry.as_path())
+            && !mime_filter_matches(file_filter.mime_filter.as_deref(), entry.mime_type())
+        {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Sorts the directory content in place according to the given settings.
+///
+/// When `group_directories` is set, directories are always listed before
+/// files independent of the sort direction. Within a group the configured
+/// [`SortMode`] decides the order, with the natural name comparator used as a
+/// stable tie-breaker for the size and time modes.
+fn sort_content(settings: &SortSettings, content: &mut [DirectoryEntry]) {
+    use std::cmp::Ordering;
+
+    content.sort_by(|a, b| {
+        if settings.group_directories && a.is_dir() != b.is_dir() {
+            return if a.is_dir() {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            };
+        }
+
+        let name = || natural_cmp(a.file_name(), b.file_name(), settings.case_insensitive);
+
+        let ordering = match settings.mode {
+            SortMode::Name => name(),
+            SortMode::Size => a.metadata().size.cmp(&b.metadata().size).then_with(name),
+            SortMode::LastModified => a
+                .metadata()
+                .last_modified
+                .cmp(&b.metadata().last_modified)
+                .then_with(name),
+            SortMode::Created => a
+                .metadata()
+                .created
+                .cmp(&b.metadata().created)
+                .then_with(name),
+            SortMode::FileType => a
+                .metadata()
+                .file_type
+                .cmp(&b.metadata().file_type)
+                .then_with(name),
+        };
+
+        match settings.direction {
+            SortDirection::Ascending => ordering,
+            SortDirection::Descending => ordering.reverse(),
+        }
+    });
+}
+
+/// Compares two names using natural (alphanumeric) ordering, so that, for
+/// example, `file2` sorts before `file10`.
+///
+/// Each name is walked as alternating runs of digit and non-digit characters.
+/// Non-digit runs compare lexicographically (optionally case-insensitively),
+/// while digit runs compare by numeric value: leading zeros are stripped, then
+/// the remaining digit length decides, and finally the digits are compared one
+/// by one.
+fn natural_cmp(a: &str, b: &str, case_insensitive: bool) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() && j < b.len() {
+        let a_digit = a[i..].starts_with(|c: char| c.is_ascii_digit());
+        let b_digit = b[j..].starts_with(|c: char| c.is_ascii_digit());
+
+        let ordering = if a_digit && b_digit {
+            let a_run = take_run(a, &mut i, true);
+            let b_run = take_run(b, &mut j, true);
+            cmp_digit_run(a_run, b_run)
+        } else if !a_digit && !b_digit {
+            let a_run = take_run(a, &mut i, false);
+            let b_run = take_run(b, &mut j, false);
+            cmp_text_run(a_run, b_run, case_insensitive)
+        } else {
+            // A digit run lines up against a non-digit run: compare the two
+            // leading characters directly and advance.
+            let a_len = a[i..].chars().next().map_or(0, char::len_utf8);
+            let b_len = b[j..].chars().next().map_or(0, char::len_utf8);
+            let ordering = cmp_text_run(&a[i..i + a_len], &b[j..j + b_len], case_insensitive);
+            i += a_len;
+            j += b_len;
+            ordering
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    // Whichever name still has bytes left sorts after the other.
+    (a.len() - i).cmp(&(b.len() - j))
+}
+
+/// Returns the run starting at `*idx` that is all digits (when `digits` is
+/// set) or all non-digits, advancing `*idx` past the run. Operates on byte
+/// offsets without allocating.
+fn take_run<'a>(s: &'a str, idx: &mut usize, digits: bool) -> &'a str {
+    let start = *idx;
+    for (offset, c) in s[start..].char_indices() {
+        if c.is_ascii_digit() != digits {
+            *idx = start + offset;
+            return &s[start..*idx];
+        }
+    }
+    *idx = s.len();
+    &s[start..*idx]
+}
+
+/// Compares two runs of non-digit characters.
+fn cmp_text_run(a: &str, b: &str, case_insensitive: bool) -> std::cmp::Ordering {
+    if case_insensitive {
+        a.chars()
+            .flat_map(char::to_lowercase)
+            .cmp(b.chars().flat_map(char::to_lowercase))
+    } else {
+        a.chars().cmp(b.chars())
+    }
+}
+
+/// Compares two runs of digit characters by numeric value.
+fn cmp_digit_run(a: &str, b: &str) -> std::cmp::Ordering {
+    let a_trimmed = a.trim_start_matches('0');
+    let b_trimmed = b.trim_start_matches('0');
+
+    a_trimmed
+        .len()
+        .cmp(&b_trimmed.len())
+        .then_with(|| a_trimmed.cmp(b_trimmed))
+        // Keep ordering stable when the numeric values are equal by preferring
+        // the value with fewer leading zeros.
+        .then_with(|| a.len().cmp(&b.len()))
+}
+
+/// Applies a single file system event to the loaded content, rebuilding the
+/// affected entry in place where possible instead of re-scanning the whole
+/// directory. Returns whether the content changed and therefore needs
+/// re-sorting.
+///
+/// Only paths that are direct children of the watched directory are handled;
+/// events for anything else are ignored.
+fn apply_fs_event(ctx: &WatchContext, content: &mut Vec<DirectoryEntry>, event: FsEvent) -> bool {
+    let in_scope = |path: &Path| path.parent() == Some(ctx.path.as_path());
+
+    // Rebuilds the entry for `path`, inserting or patching it when it passes
+    // the active filters and removing any stale entry otherwise.
+    let mut upsert = |content: &mut Vec<DirectoryEntry>, path: &Path| -> bool {
+        if !in_scope(path) {
+            return false;
+        }
+
+        let entry = DirectoryEntry::from_path(&ctx.config, path, &*ctx.file_system);
+
+        if entry_passes_filters(
+            &ctx.config,
+            &entry,
+            ctx.include_files,
+            ctx.file_filter.as_ref(),
+            &ctx.ignore_rules,
+        ) {
+            if let Some(existing) = content.iter_mut().find(|e| e.as_path() == path) {
+                *existing = entry;
+            } else {
+                content.push(entry);
+            }
+            true
+        } else {
+            remove_entry(content, path)
+        }
+    };
+
+    match event {
+        FsEvent::Created(path) | FsEvent::Modified(path) => upsert(content, &path),
+        FsEvent::Removed(path) => remove_entry(content, &path),
+        FsEvent::Renamed { from, to } => {
+            let removed = remove_entry(content, &from);
+            let added = upsert(content, &to);
+            removed || added
+        }
+    }
+}
+
+/// Removes the entry for the given path from the content, returning whether
+/// an entry was actually removed.
+fn remove_entry(content: &mut Vec<DirectoryEntry>, path: &Path) -> bool {
+    let before = content.len();
+    content.retain(|entry| entry.as_path() != path);
+    content.len() != before
+}
+
 /// Loads the contents of the given directory.
 fn load_directory(
     config: &FileDialogConfig,
@@ -352,51 +1151,146 @@ fn load_directory(
     file_filter: Option<&FileFilter>,
     file_system: &dyn FileSystem,
 ) -> io::Result<Vec<DirectoryEntry>> {
+    let ignore_rules = if config.respect_gitignore {
+        build_ignore_rules(config, path, file_system)
+    } else {
+        Vec::new()
+    };
+
     let mut result: Vec<DirectoryEntry> = Vec::new();
-    for path in file_system.read_dir(path)? {
-        let entry = DirectoryEntry::from_path(config, &path, file_system);
+    for entry_path in file_system.read_dir(path)? {
+        let entry = DirectoryEntry::from_path(config, &entry_path, file_system);
 
-        if !config.storage.show_system_files && entry.is_system_file() {
-            continue;
+        if entry_passes_filters(config, &entry, include_files, file_filter, &ignore_rules) {
+            result.push(entry);
         }
+    }
 
-        if !include_files && entry.is_file() {
-            continue;
+    sort_content(&SortSettings::from_config(config), &mut result);
+
+    Ok(result)
+}
+
+/// Walks the given directory on the calling (loader) thread and streams
+/// batches of filtered entries over `tx` as they are discovered, so the UI
+/// can display partial results while large or high-latency directories are
+/// still being read. Sorting is deferred to the UI once the full listing has
+/// been received (see [`DirectoryContent::update`]).
+///
+/// A read error is forwarded over the channel and ends the stream.
+fn stream_directory(
+    config: &FileDialogConfig,
+    path: &Path,
+    include_files: bool,
+    file_filter: Option<&FileFilter>,
+    file_system: &(dyn FileSystem + Send + Sync),
+    tx: &mpsc::Sender<Result<Vec<DirectoryEntry>, io::Error>>,
+) {
+    let paths = match file_system.read_dir(path) {
+        Ok(paths) => paths,
+        Err(err) => {
+            let _ = tx.send(Err(err));
+            return;
+        }
+    };
+
+    let ignore_rules = if config.respect_gitignore {
+        build_ignore_rules(config, path, file_system)
+    } else {
+        Vec::new()
+    };
+
+    let parallelism = resolve_parallelism(config.load_parallelism);
+
+    if parallelism <= 1 {
+        stream_paths(config, &paths, include_files, file_filter, file_system, &ignore_rules, tx);
+        return;
+    }
+
+    // Fan the per-path `DirectoryEntry::from_path` work — several synchronous
+    // `FileSystem` calls each — out across a bounded pool of worker threads so
+    // stat-heavy or high-latency directories no longer serialize. Each worker
+    // streams its own batches, so partial results still appear as they land.
+    let ignore_rules = &ignore_rules;
+    let chunk_size = paths.len().div_ceil(parallelism).max(1);
+    std::thread::scope(|scope| {
+        for chunk in paths.chunks(chunk_size) {
+            let tx = tx.clone();
+            scope.spawn(move || {
+                stream_paths(
+                    config,
+                    chunk,
+                    include_files,
+                    file_filter,
+                    file_system,
+                    ignore_rules,
+                    &tx,
+                );
+            });
         }
+    });
+}
+
+/// Builds, filters and streams entries for the given paths on the calling
+/// thread, emitting batches of at most [`LOAD_BATCH_SIZE`] entries.
+fn stream_paths(
+    config: &FileDialogConfig,
+    paths: &[PathBuf],
+    include_files: bool,
+    file_filter: Option<&FileFilter>,
+    file_system: &(dyn FileSystem + Send + Sync),
+    ignore_rules: &[IgnoreRule],
+    tx: &mpsc::Sender<Result<Vec<DirectoryEntry>, io::Error>>,
+) {
+    let mut batch: Vec<DirectoryEntry> = Vec::with_capacity(LOAD_BATCH_SIZE);
+    for entry_path in paths {
+        let entry = DirectoryEntry::from_path(config, entry_path, file_system);
 
-        if !config.storage.show_hidden && entry.is_hidden() {
+        if !entry_passes_filters(config, &entry, include_files, file_filter, ignore_rules) {
             continue;
         }
 
-        if let Some(file_filter) = file_filter {
-            if entry.is_file() && !(file_filter.filter)(entry.as_path()) {
-                continue;
+        batch.push(entry);
+
+        if batch.len() >= LOAD_BATCH_SIZE {
+            // If the receiver is gone the dialog moved on; stop early.
+            if tx.send(Ok(std::mem::take(&mut batch))).is_err() {
+                return;
             }
         }
-
-        result.push(entry);
     }
 
-    result.sort_by(|a, b| {
-        if a.is_dir() == b.is_dir() {
-            a.file_name().cmp(b.file_name())
-        } else if a.is_dir() {
-            std::cmp::Ordering::Less
-        } else {
-            std::cmp::Ordering::Greater
-        }
-    });
+    if !batch.is_empty() {
+        let _ = tx.send(Ok(batch));
+    }
+}
 
-    Ok(result)
+/// Resolves the configured [`FileDialogConfig::load_parallelism`] knob into a
+/// concrete worker count: `0` picks a default from the available core count,
+/// any other value is used verbatim (`1` keeps the sequential behavior).
+fn resolve_parallelism(configured: usize) -> usize {
+    if configured == 0 {
+        std::thread::available_parallelism()
+            .map(std::num::NonZeroUsize::get)
+            .unwrap_or(1)
+    } else {
+        configured
+    }
 }
 
 
 /// Generates the icon for the specific path.
 /// The default icon configuration is taken into account, as well as any configured
-/// file icon filters.
-fn gen_path_icon(config: &FileDialogConfig, path: &Path, file_system: &dyn FileSystem) -> String {
+/// file icon filters. An icon filter matches either its path predicate or,
+/// when it specifies a MIME pattern (e.g. `image/*`), the resolved MIME type.
+fn gen_path_icon(
+    config: &FileDialogConfig,
+    path: &Path,
+    file_system: &dyn FileSystem,
+    mime: Option<&str>,
+) -> String {
     for def in &config.file_icon_filters {
-        if (def.filter)(path) {
+        if (def.filter)(path) || mime_filter_matches(def.mime_filter.as_deref(), mime) {
             return def.icon.clone();
         }
     }
@@ -407,3 +1301,248 @@ fn gen_path_icon(config: &FileDialogConfig, path: &Path, file_system: &dyn FileS
         config.default_file_icon.clone()
     }
 }
+
+/// Resolves the MIME type of a file, first from its extension and, when
+/// `config.sniff_mime_type` is enabled and the extension is unknown, by
+/// sniffing the leading bytes for a magic-number match.
+fn resolve_mime(
+    config: &FileDialogConfig,
+    path: &Path,
+    file_system: &dyn FileSystem,
+) -> Option<String> {
+    if let Some(mime) = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(mime_from_extension)
+    {
+        return Some(mime.to_owned());
+    }
+
+    if config.sniff_mime_type {
+        if let Some(mime) = sniff_mime(path, file_system) {
+            return Some(mime.to_owned());
+        }
+    }
+
+    None
+}
+
+/// Maps a file extension to a MIME type. Covers the common types a file
+/// browser needs to distinguish; unknown extensions fall through to sniffing.
+fn mime_from_extension(ext: &str) -> Option<&'static str> {
+    Some(match ext.to_ascii_lowercase().as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "mkv" => "video/x-matroska",
+        "mov" => "video/quicktime",
+        "avi" => "video/x-msvideo",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "ogg" => "audio/ogg",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "csv" => "text/csv",
+        "md" => "text/markdown",
+        "txt" | "text" => "text/plain",
+        "rs" => "text/x-rust",
+        "toml" => "application/toml",
+        _ => return None,
+    })
+}
+
+/// Sniffs the leading bytes of a file for a magic-number match. Returns the
+/// detected MIME type, or `None` if the header is unrecognised or unreadable.
+fn sniff_mime(path: &Path, file_system: &dyn FileSystem) -> Option<&'static str> {
+    // Read only the leading bytes through the `FileSystem` abstraction so
+    // sniffing works on virtual and remote file systems rather than the local
+    // disk, and so a multi-gigabyte extensionless file doesn't get pulled
+    // into memory just to inspect its header.
+    let header = file_system.read_prefix(path, 16).ok()?;
+
+    let starts_with = |prefix: &[u8]| header.starts_with(prefix);
+
+    if starts_with(&[0x89, b'P', b'N', b'G']) {
+        Some("image/png")
+    } else if starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if starts_with(b"GIF87a") || starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else if starts_with(&[b'P', b'K', 0x03, 0x04]) {
+        Some("application/zip")
+    } else if starts_with(&[0x1F, 0x8B]) {
+        Some("application/gzip")
+    } else if header.is_ascii() && !header.is_empty() {
+        Some("text/plain")
+    } else {
+        None
+    }
+}
+
+/// Returns whether a MIME filter pattern (such as `image/*`) matches a
+/// resolved MIME type. A missing pattern or missing MIME type never matches.
+fn mime_filter_matches(pattern: Option<&str>, mime: Option<&str>) -> bool {
+    match (pattern, mime) {
+        (Some(pattern), Some(mime)) => mime_matches(pattern, mime),
+        _ => false,
+    }
+}
+
+/// Matches a MIME pattern against a MIME type, where `*` is a wildcard for
+/// the type or subtype, for example `image/*` or `*/*`.
+fn mime_matches(pattern: &str, mime: &str) -> bool {
+    let (pattern_type, pattern_sub) = pattern.split_once('/').unwrap_or((pattern, "*"));
+    let (mime_type, mime_sub) = mime.split_once('/').unwrap_or((mime, ""));
+
+    (pattern_type == "*" || pattern_type.eq_ignore_ascii_case(mime_type))
+        && (pattern_sub == "*" || pattern_sub.eq_ignore_ascii_case(mime_sub))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    fn entry(path: &str, is_directory: bool) -> DirectoryEntry {
+        DirectoryEntry {
+            path: PathBuf::from(path),
+            is_directory,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn natural_cmp_orders_numbers_by_value() {
+        assert_eq!(natural_cmp("file2", "file10", false), Ordering::Less);
+        assert_eq!(natural_cmp("file10", "file2", false), Ordering::Greater);
+        assert_eq!(natural_cmp("file2", "file2", false), Ordering::Equal);
+        // A name that is a prefix of another (ending where the other's digit run starts) sorts first.
+        assert_eq!(natural_cmp("file", "file2", false), Ordering::Less);
+    }
+
+    #[test]
+    fn natural_cmp_breaks_equal_value_ties_by_leading_zeros() {
+        // Equal numeric value: fewer leading zeros sorts first, keeping order stable.
+        assert_eq!(natural_cmp("file2", "file02", false), Ordering::Less);
+        assert_eq!(cmp_digit_run("005", "5"), Ordering::Greater);
+        assert_eq!(cmp_digit_run("5", "5"), Ordering::Equal);
+    }
+
+    #[test]
+    fn natural_cmp_respects_case_insensitivity() {
+        assert_eq!(natural_cmp("File", "file", true), Ordering::Equal);
+        assert_ne!(natural_cmp("File", "file", false), Ordering::Equal);
+    }
+
+    #[test]
+    fn ignore_rule_parse_flags() {
+        let base = Path::new("/repo");
+        assert!(IgnoreRule::parse("# comment", base).is_none());
+        assert!(IgnoreRule::parse("   ", base).is_none());
+
+        let neg = IgnoreRule::parse("!keep.log", base).unwrap();
+        assert!(neg.is_negation);
+        assert_eq!(neg.pattern, "keep.log");
+
+        let dir = IgnoreRule::parse("build/", base).unwrap();
+        assert!(dir.dir_only);
+        assert!(!dir.anchored);
+        assert_eq!(dir.pattern, "build");
+
+        let anchored = IgnoreRule::parse("/target", base).unwrap();
+        assert!(anchored.anchored);
+        assert_eq!(anchored.pattern, "target");
+
+        assert!(IgnoreRule::parse("a/b", base).unwrap().anchored);
+    }
+
+    #[test]
+    fn wildcard_match_globs() {
+        assert!(wildcard_match("*.rs", "main.rs"));
+        assert!(!wildcard_match("*.rs", "main.txt"));
+        assert!(wildcard_match("f?o", "foo"));
+        assert!(!wildcard_match("f?o", "fooo"));
+    }
+
+    #[test]
+    fn match_segments_handles_double_star() {
+        assert!(match_segments(&["**", "a.rs"], &["src", "a.rs"]));
+        assert!(match_segments(&["src", "**"], &["src", "a", "b.rs"]));
+        assert!(!match_segments(&["src", "a.rs"], &["lib", "a.rs"]));
+    }
+
+    #[test]
+    fn anchored_dir_only_rule_suppresses_contents() {
+        let rule = IgnoreRule::parse("/target/", Path::new("/repo")).unwrap();
+        // The directory itself.
+        assert!(rule.matches(Path::new("/repo/target"), true));
+        // A file named `target` is not matched by the dir-only rule.
+        assert!(!rule.matches(Path::new("/repo/target"), false));
+        // Contents of the ignored directory stay hidden.
+        assert!(rule.matches(Path::new("/repo/target/debug/app"), false));
+        // A `target` nested elsewhere is not anchored to the base.
+        assert!(!rule.matches(Path::new("/repo/src/target"), true));
+    }
+
+    #[test]
+    fn negation_rule_reincludes() {
+        let rules = vec![
+            IgnoreRule::parse("*.log", Path::new("/repo")).unwrap(),
+            IgnoreRule::parse("!keep.log", Path::new("/repo")).unwrap(),
+        ];
+        assert!(is_ignored(&rules, &entry("/repo/app.log", false)));
+        assert!(!is_ignored(&rules, &entry("/repo/keep.log", false)));
+    }
+
+    #[test]
+    fn mime_matches_wildcards() {
+        assert!(mime_matches("image/*", "image/png"));
+        assert!(mime_matches("image/*", "image/svg+xml"));
+        assert!(!mime_matches("image/*", "text/plain"));
+        assert!(mime_matches("*/*", "anything/else"));
+        assert!(mime_matches("image/svg+xml", "image/svg+xml"));
+        assert!(!mime_matches("image/svg+xml", "image/png"));
+    }
+
+    #[test]
+    fn mime_from_extension_maps_registered_types() {
+        assert_eq!(mime_from_extension("PNG"), Some("image/png"));
+        assert_eq!(mime_from_extension("rs"), Some("text/x-rust"));
+        assert_eq!(mime_from_extension("toml"), Some("application/toml"));
+        assert_eq!(mime_from_extension("nonesuch"), None);
+    }
+
+    #[test]
+    fn parse_excludes_file_setting_reads_core_section() {
+        let config = "[user]\n\tname = test\n[core]\n\texcludesfile = ~/.ignore\n\tautocrlf = false\n";
+        assert_eq!(
+            parse_excludes_file_setting(config),
+            Some("~/.ignore".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_excludes_file_setting_ignores_other_sections() {
+        let config = "[includeIf \"gitdir:~/work/\"]\n\texcludesfile = /wrong\n";
+        assert_eq!(parse_excludes_file_setting(config), None);
+    }
+
+    #[test]
+    fn parse_excludes_file_setting_missing_key() {
+        assert_eq!(parse_excludes_file_setting("[core]\n\tautocrlf = false\n"), None);
+    }
+}